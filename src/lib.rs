@@ -0,0 +1,1830 @@
+//! An async multi-producer multi-consumer channel, where each message can be received by only
+//! one of all existing consumers.
+//!
+//! There are two kinds of channels:
+//!
+//! 1. [Bounded][`bounded()`] channel with limited capacity.
+//! 2. [Unbounded][`unbounded()`] channel with unlimited capacity.
+//!
+//! A channel has the `Sender` and `Receiver` side. Both sides are cloneable and can be shared
+//! among multiple threads.
+//!
+//! When all `Sender`s or all `Receiver`s are dropped, the channel becomes closed. When a
+//! channel is closed, no more messages can be sent, but remaining messages can still be received.
+//!
+//! The channel can also be closed manually by calling [`Sender::close()`] or
+//! [`Receiver::close()`].
+//!
+//! # Examples
+//!
+//! ```
+//! use async_channel::bounded;
+//! use futures_lite::future::block_on;
+//!
+//! let (s, r) = bounded(1);
+//!
+//! block_on(s.send(1)).unwrap();
+//! assert_eq!(block_on(r.recv()), Ok(1));
+//!
+//! drop(s);
+//! assert_eq!(block_on(r.recv()), Err(async_channel::RecvError));
+//! ```
+
+#![warn(missing_docs, missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+#![doc(test(attr(deny(warnings))))]
+
+use std::error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Weak;
+use std::task::{Context, Poll};
+
+use concurrent_queue::{ConcurrentQueue, PopError, PushError};
+use event_listener::{Event, EventListener};
+#[cfg(feature = "std")]
+use event_listener::Listener;
+use futures_core::stream::Stream;
+#[cfg(feature = "std")]
+use futures_lite::future;
+#[cfg(feature = "std")]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+mod timer;
+
+mod select;
+pub use select::{Select, SelectedOperation};
+
+/// Creates a bounded channel.
+///
+/// The created channel has space to hold at most `cap` messages at a time.
+///
+/// Passing `cap = 0` creates a zero-capacity "rendezvous" channel, where a send only completes
+/// once a receiver is ready to take the message directly from the sender, with no buffering in
+/// between. This mirrors the "zero" channel flavor found in `crossbeam-channel`.
+///
+/// Note that a rendezvous handoff isn't cancellation-safe: if the `recv()` call or [`Select`] that
+/// made a sender's `send()` complete stops waiting before it takes the message (for example
+/// because the `Select` resolved on a different operation), the message can instead go to a
+/// different, later `recv()` call.
+///
+/// # Panics
+///
+/// Capacity cannot be larger than [`usize::MAX`] / 4 - [this is a limitation of the underlying
+/// concurrent queue], but is otherwise unconstrained (unlike earlier versions of this crate,
+/// `cap = 0` is supported).
+///
+/// # Examples
+///
+/// ```
+/// use async_channel::{bounded, TryRecvError, TrySendError};
+///
+/// let (s, r) = bounded(1);
+///
+/// assert_eq!(s.try_send(10), Ok(()));
+/// assert_eq!(s.try_send(20), Err(TrySendError::Full(20)));
+///
+/// assert_eq!(r.try_recv(), Ok(10));
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+/// ```
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Channel::with_capacity(Some(cap)));
+    (
+        Sender {
+            channel: channel.clone(),
+        },
+        Receiver {
+            channel,
+            stream_listener: None,
+            stream_parked: false,
+        },
+    )
+}
+
+/// Creates an unbounded channel.
+///
+/// The created channel can hold an unlimited number of messages.
+///
+/// # Examples
+///
+/// ```
+/// use async_channel::{unbounded, TryRecvError};
+///
+/// let (s, r) = unbounded();
+///
+/// assert_eq!(s.try_send(10), Ok(()));
+/// assert_eq!(s.try_send(20), Ok(()));
+///
+/// assert_eq!(r.try_recv(), Ok(10));
+/// assert_eq!(r.try_recv(), Ok(20));
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+/// ```
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Channel::with_capacity(None));
+    (
+        Sender {
+            channel: channel.clone(),
+        },
+        Receiver {
+            channel,
+            stream_listener: None,
+            stream_parked: false,
+        },
+    )
+}
+
+/// Creates a receiver that delivers exactly one [`Instant`], `duration` from now.
+///
+/// The returned receiver is a regular [`Receiver`], so it can be raced against other channels
+/// through [`Select`] — the common pattern of selecting over a data channel and a timeout.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use async_channel::{after, unbounded};
+/// use futures_lite::future::block_on;
+///
+/// let (_s, r) = unbounded::<()>();
+/// let timeout = after(Duration::from_millis(1));
+///
+/// block_on(async {
+///     let mut select = async_channel::Select::new();
+///     select.recv(&r);
+///     select.recv(&timeout);
+///
+///     let op = select.select().await;
+///     assert_eq!(op.index(), 1);
+/// });
+/// ```
+#[cfg(feature = "std")]
+pub fn after(duration: Duration) -> Receiver<Instant> {
+    let (s, r) = bounded(1);
+    thread::spawn(move || {
+        thread::sleep(duration);
+        // If the receiver was already dropped there is nothing left to deliver to.
+        let _ = s.try_send(Instant::now());
+    });
+    r
+}
+
+/// Creates a receiver that delivers an [`Instant`] every `duration`, starting `duration` from
+/// now.
+///
+/// Deadlines are anchored to the instant `tick()` was called rather than to the previous
+/// delivery, so the interval does not accumulate drift even if a tick is occasionally picked up
+/// late. A tick that isn't consumed before the next one is due stays pending, and the ticks that
+/// fall due while it is pending are dropped rather than queued.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use async_channel::tick;
+/// use futures_lite::future::block_on;
+///
+/// let ticker = tick(Duration::from_millis(1));
+/// block_on(async {
+///     ticker.recv().await.unwrap();
+///     ticker.recv().await.unwrap();
+/// });
+/// ```
+#[cfg(feature = "std")]
+pub fn tick(duration: Duration) -> Receiver<Instant> {
+    let (s, r) = bounded(1);
+    thread::spawn(move || {
+        let start = Instant::now();
+        let mut ticks: u32 = 1;
+        loop {
+            let deadline = start + duration * ticks;
+            if let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                thread::sleep(remaining);
+            }
+
+            match s.try_send(Instant::now()) {
+                Ok(()) => {}
+                // The previous tick hasn't been picked up yet — skip this one rather than
+                // queuing it up, matching the "don't accumulate missed ticks" contract.
+                Err(TrySendError::Full(_)) => {}
+                Err(TrySendError::Closed(_)) => break,
+            }
+            ticks = ticks.wrapping_add(1);
+        }
+    });
+    r
+}
+
+/// The backing queue for a channel: either a regular ring buffer, or (for `bounded(0)`) a
+/// rendezvous handshake between exactly one sender and one waiting receiver.
+enum Queue<T> {
+    /// A buffered queue, used for `unbounded()` and `bounded(cap)` with `cap > 0`.
+    ///
+    /// Boxed so that a rendezvous channel's `Channel<T>` — which never touches this variant —
+    /// doesn't pay for `ConcurrentQueue`'s much larger inline size.
+    Buffered(Box<ConcurrentQueue<T>>),
+    /// A zero-capacity rendezvous queue, used for `bounded(0)`.
+    Rendezvous(Rendezvous<T>),
+}
+
+/// State for a zero-capacity rendezvous channel.
+///
+/// A sender may only hand off a message while at least one receiver is parked waiting for one;
+/// the message is placed directly into `slot` and the parked receiver is woken to take it, with
+/// no intermediate buffering.
+///
+/// # Cancellation caveat
+///
+/// The handoff is not tied to any particular parked receiver, only to the fact that
+/// `waiting_receivers` is positive. If the waiter that caused the count to go positive (a
+/// `Recv`/`RecvTimeout` future, or a [`Select`](crate::Select) waiting on this receiver) stops
+/// waiting before it takes the message (for example, the `Select` resolved on another operation,
+/// or the timeout elapsed) after a sender has already placed a message in `slot`, that
+/// message is not returned to the sender — it sits in `slot` and is silently handed to whichever
+/// receiver calls `pop()` next, even one that never itself registered as waiting. A `send().await`
+/// can therefore complete believing it paired with one receiver while the value is actually
+/// picked up by an unrelated, later one. This is a known gap in the handshake's cancellation
+/// safety; closing it would mean reclaiming or re-offering a slot abandoned by a cancelled
+/// receiver, which `Rendezvous` does not currently do.
+struct Rendezvous<T> {
+    /// The number of receivers currently parked waiting for a message.
+    waiting_receivers: AtomicUsize,
+    /// The handed-off message, if a sender has placed one for a waiting receiver to claim.
+    slot: std::sync::Mutex<Option<T>>,
+    /// Whether the channel has been closed.
+    closed: std::sync::atomic::AtomicBool,
+}
+
+impl<T> Rendezvous<T> {
+    fn new() -> Self {
+        Rendezvous {
+            waiting_receivers: AtomicUsize::new(0),
+            slot: std::sync::Mutex::new(None),
+            closed: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Closes the queue, returning `true` if this call closed it.
+    fn close(&self) -> bool {
+        !self.closed.swap(true, Ordering::SeqCst)
+    }
+
+    /// Tries to hand `msg` directly to a parked receiver. Succeeds only if a receiver is parked
+    /// and the slot is currently empty.
+    fn push(&self, msg: T) -> Result<(), PushError<T>> {
+        if self.is_closed() {
+            return Err(PushError::Closed(msg));
+        }
+        if self.waiting_receivers.load(Ordering::SeqCst) == 0 {
+            return Err(PushError::Full(msg));
+        }
+        let mut slot = self.slot.lock().unwrap();
+        if slot.is_some() {
+            return Err(PushError::Full(msg));
+        }
+        *slot = Some(msg);
+        Ok(())
+    }
+
+    /// Takes a message out of the slot, if a sender has placed one.
+    fn pop(&self) -> Result<T, PopError> {
+        let mut slot = self.slot.lock().unwrap();
+        if let Some(msg) = slot.take() {
+            return Ok(msg);
+        }
+        if self.is_closed() {
+            Err(PopError::Closed)
+        } else {
+            Err(PopError::Empty)
+        }
+    }
+
+    /// Returns `true` if a send could complete right now, i.e. a receiver is parked and the
+    /// slot is free for a sender to fill. Unlike [`Rendezvous::push()`] this never closes over
+    /// the slot, so it's safe to use as a cheap readiness check (e.g. from [`crate::Select`]).
+    fn can_push(&self) -> bool {
+        self.waiting_receivers.load(Ordering::SeqCst) > 0 && self.slot.lock().unwrap().is_none()
+    }
+}
+
+/// Internal state shared between all `Sender`s and `Receiver`s of a channel.
+struct Channel<T> {
+    /// The queue backing the channel.
+    queue: Queue<T>,
+
+    /// Send operations waiting while the channel is full.
+    send_ops: Event,
+
+    /// Receive operations waiting while the channel is empty.
+    recv_ops: Event,
+
+    /// Stream operations while the channel is empty.
+    stream_ops: Event,
+
+    /// The number of currently active `Sender`s.
+    sender_count: AtomicUsize,
+
+    /// The number of currently active `Receiver`s.
+    receiver_count: AtomicUsize,
+}
+
+impl<T> Channel<T> {
+    fn with_capacity(cap: Option<usize>) -> Self {
+        let queue = match cap {
+            Some(0) => Queue::Rendezvous(Rendezvous::new()),
+            Some(cap) => Queue::Buffered(Box::new(ConcurrentQueue::bounded(cap))),
+            None => Queue::Buffered(Box::new(ConcurrentQueue::unbounded())),
+        };
+        Channel {
+            queue,
+            send_ops: Event::new(),
+            recv_ops: Event::new(),
+            stream_ops: Event::new(),
+            sender_count: AtomicUsize::new(1),
+            receiver_count: AtomicUsize::new(1),
+        }
+    }
+
+    fn push(&self, msg: T) -> Result<(), PushError<T>> {
+        match &self.queue {
+            Queue::Buffered(q) => q.push(msg),
+            Queue::Rendezvous(q) => q.push(msg),
+        }
+    }
+
+    fn pop(&self) -> Result<T, PopError> {
+        match &self.queue {
+            Queue::Buffered(q) => q.pop(),
+            Queue::Rendezvous(q) => q.pop(),
+        }
+    }
+
+    fn close(&self) -> bool {
+        let closed = match &self.queue {
+            Queue::Buffered(q) => q.close(),
+            Queue::Rendezvous(q) => q.close(),
+        };
+        if closed {
+            self.send_ops.notify(usize::MAX);
+            self.recv_ops.notify(usize::MAX);
+            self.stream_ops.notify(usize::MAX);
+        }
+        closed
+    }
+
+    fn is_closed(&self) -> bool {
+        match &self.queue {
+            Queue::Buffered(q) => q.is_closed(),
+            Queue::Rendezvous(q) => q.is_closed(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match &self.queue {
+            // A rendezvous channel never buffers anything: its length is always zero.
+            Queue::Buffered(q) => q.is_empty(),
+            Queue::Rendezvous(_) => true,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        match &self.queue {
+            Queue::Buffered(q) => q.is_full(),
+            // A rendezvous channel has no room to buffer a message until a receiver is parked,
+            // so it reports itself as always full.
+            Queue::Rendezvous(_) => true,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match &self.queue {
+            Queue::Buffered(q) => q.len(),
+            Queue::Rendezvous(_) => 0,
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        match &self.queue {
+            Queue::Buffered(q) => q.capacity(),
+            Queue::Rendezvous(_) => Some(0),
+        }
+    }
+
+    /// Returns `true` if a send could complete right now.
+    ///
+    /// This is deliberately distinct from `!is_full()`: a rendezvous channel reports `is_full()`
+    /// as always `true` (per its public API contract), but a send still goes through as soon as
+    /// a receiver is parked waiting. [`Select`](crate::Select) needs the real answer to decide
+    /// whether a send branch is ready.
+    fn send_ready(&self) -> bool {
+        match &self.queue {
+            Queue::Buffered(q) => !q.is_full() || q.is_closed(),
+            Queue::Rendezvous(q) => q.can_push() || q.is_closed(),
+        }
+    }
+
+    /// Returns `true` if a receive could complete right now.
+    ///
+    /// This is deliberately distinct from `!is_empty()`: a rendezvous channel reports
+    /// `is_empty()` as always `true` (per its public API contract), but a receive still goes
+    /// through as soon as a sender has placed a message in the handoff slot.
+    /// [`Select`](crate::Select) needs the real answer to decide whether a recv branch is ready.
+    fn recv_ready(&self) -> bool {
+        match &self.queue {
+            Queue::Buffered(q) => !q.is_empty() || q.is_closed(),
+            Queue::Rendezvous(q) => q.slot.lock().unwrap().is_some() || q.is_closed(),
+        }
+    }
+
+    /// Registers that a receiver has started parking, waiting for a sender to hand off a
+    /// message directly. No-op for buffered channels.
+    fn mark_receiver_waiting(&self) {
+        if let Queue::Rendezvous(q) = &self.queue {
+            q.waiting_receivers.fetch_add(1, Ordering::SeqCst);
+            // Let any parked sender know a receiver is now ready to take a handoff.
+            self.send_ops.notify(1);
+        }
+    }
+
+    /// Undoes a previous call to `mark_receiver_waiting` once the receiver stops waiting
+    /// (whether because it got a message or gave up).
+    fn unmark_receiver_waiting(&self) {
+        if let Queue::Rendezvous(q) = &self.queue {
+            q.waiting_receivers.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The sending side of a channel.
+///
+/// Senders can be cloned and shared among threads. When all senders associated with a channel
+/// are dropped, the channel becomes closed.
+///
+/// The channel can also be closed manually by calling [`Sender::close()`].
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Attempts to send a message into the channel.
+    ///
+    /// If the channel is full, or if a rendezvous channel currently has no receiver parked
+    /// waiting for a handoff, this method returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::{bounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.try_send(3), Err(TrySendError::Closed(3)));
+    /// ```
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        match self.channel.push(msg) {
+            Ok(()) => {
+                self.channel.recv_ops.notify(1);
+                self.channel.stream_ops.notify(usize::MAX);
+                Ok(())
+            }
+            Err(PushError::Full(msg)) => Err(TrySendError::Full(msg)),
+            Err(PushError::Closed(msg)) => Err(TrySendError::Closed(msg)),
+        }
+    }
+
+    /// Sends a message into the channel.
+    ///
+    /// If the channel is full, this method waits until there is space for a message, or until
+    /// the channel is closed. For a rendezvous (`bounded(0)`) channel, this waits until a
+    /// receiver is parked waiting and the message can be handed to it directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::{bounded, SendError};
+    /// use futures_lite::future::block_on;
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// block_on(async {
+    ///     assert_eq!(s.send(1).await, Ok(()));
+    ///     drop(r);
+    ///     assert_eq!(s.send(2).await, Err(SendError(2)));
+    /// });
+    /// ```
+    pub fn send(&self, msg: T) -> Send<'_, T> {
+        Send {
+            sender: self,
+            listener: None,
+            msg: Some(msg),
+        }
+    }
+
+    /// Sends a message into the channel, blocking the current thread until it is sent.
+    ///
+    /// This method should not be used in an async context.
+    #[cfg(feature = "std")]
+    pub fn send_blocking(&self, msg: T) -> Result<(), SendError<T>> {
+        future::block_on(self.send(msg))
+    }
+
+    /// Sends a message into the channel, waiting at most `timeout` for space to become
+    /// available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use async_channel::{bounded, SendTimeoutError};
+    /// use futures_lite::future::block_on;
+    ///
+    /// let (s, _r) = bounded(1);
+    ///
+    /// block_on(async {
+    ///     s.send(1).await.unwrap();
+    ///     assert_eq!(
+    ///         s.send_timeout(2, Duration::from_millis(1)).await,
+    ///         Err(SendTimeoutError::Timeout(2)),
+    ///     );
+    /// });
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> SendTimeout<'_, T> {
+        SendTimeout {
+            sender: self,
+            listener: None,
+            timer: None,
+            deadline: Instant::now() + timeout,
+            msg: Some(msg),
+        }
+    }
+
+    /// Sends a message into the channel, blocking the current thread for at most `timeout`
+    /// waiting for space to become available.
+    ///
+    /// This method should not be used in an async context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use async_channel::{bounded, SendTimeoutError};
+    ///
+    /// let (s, _r) = bounded(1);
+    ///
+    /// s.send_blocking(1).unwrap();
+    /// assert_eq!(
+    ///     s.send_timeout_blocking(2, Duration::from_millis(1)),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn send_timeout_blocking(
+        &self,
+        msg: T,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let deadline = Instant::now() + timeout;
+        let mut msg = msg;
+        let mut listener = None;
+
+        loop {
+            match self.try_send(msg) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(msg)) => return Err(SendTimeoutError::Closed(msg)),
+                Err(TrySendError::Full(m)) => msg = m,
+            }
+
+            match listener.take() {
+                // A listener registered just now might have missed a notification that fired in
+                // the gap between the `try_send` above and `listen()` below, so loop back and
+                // retry `try_send` before ever waiting on it — mirrors how `poll_recv` only waits
+                // on a listener from a previous iteration, never one it just created.
+                None => listener = Some(self.channel.send_ops.listen()),
+                Some(l) => {
+                    // `wait_deadline` returning `None` means the deadline passed; dropping `l`
+                    // here deregisters it from the waiter list so a later send doesn't wake a
+                    // thread that already gave up.
+                    if l.wait_deadline(deadline).is_none() {
+                        return Err(SendTimeoutError::Timeout(msg));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Closes the channel.
+    ///
+    /// Returns `true` if this call closed the channel, or `false` if it was already closed.
+    pub fn close(&self) -> bool {
+        self.channel.close()
+    }
+
+    /// Returns `true` if the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.channel.is_closed()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.channel.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.channel.is_full()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.channel.len()
+    }
+
+    /// Returns the channel capacity, if any.
+    pub fn capacity(&self) -> Option<usize> {
+        self.channel.capacity()
+    }
+
+    /// Returns the number of receivers for the channel.
+    pub fn receiver_count(&self) -> usize {
+        self.channel.receiver_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of senders for the channel.
+    pub fn sender_count(&self) -> usize {
+        self.channel.sender_count.load(Ordering::SeqCst)
+    }
+
+    /// Downgrades the sender to a [`WeakSender`].
+    #[cfg(feature = "std")]
+    pub fn downgrade(&self) -> WeakSender<T> {
+        WeakSender {
+            channel: Arc::downgrade(&self.channel),
+        }
+    }
+
+    /// Returns `true` if senders/receivers belong to the same channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::unbounded;
+    ///
+    /// let (s, _r) = unbounded::<()>();
+    /// let (s2, _r2) = unbounded::<()>();
+    ///
+    /// assert!(s.same_channel(&s.clone()));
+    /// assert!(!s.same_channel(&s2));
+    /// ```
+    pub fn same_channel(&self, other: &Sender<T>) -> bool {
+        Arc::ptr_eq(&self.channel, &other.channel)
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.channel.sender_count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.channel.close();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sender { .. }")
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        let count = self.channel.sender_count.fetch_add(1, Ordering::Relaxed);
+        // Prevent overflow through mem::forget() abuse.
+        if count > usize::MAX / 2 {
+            std::process::abort();
+        }
+        Sender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+/// The receiving side of a channel.
+///
+/// Receivers can be cloned and shared among threads. When all receivers associated with a
+/// channel are dropped, the channel becomes closed.
+///
+/// The channel can also be closed manually by calling [`Receiver::close()`].
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+
+    /// Listener used by this receiver's `Stream` implementation.
+    ///
+    /// Unlike `Recv`/`RecvTimeout`, which own their listener for the lifetime of a single
+    /// `.await`, `poll_next()` is called repeatedly through a `&mut self` with no future of its
+    /// own to hold state in, so the listener has to live here instead. Keeping it pinned to the
+    /// receiver (rather than a per-call local) is what lets a registered wakeup survive from one
+    /// `poll_next()` call to the next.
+    stream_listener: Option<EventListener>,
+
+    /// Whether this receiver is currently registered as waiting in `channel.stream_ops`.
+    stream_parked: bool,
+}
+
+impl<T> Receiver<T> {
+    /// Attempts to receive a message from the channel.
+    ///
+    /// If the channel is empty, or if a rendezvous channel currently has no handed-off message
+    /// waiting, this method returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(s.try_send(1), Ok(()));
+    ///
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// drop(s);
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Closed));
+    /// ```
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        match self.channel.pop() {
+            Ok(msg) => {
+                self.channel.send_ops.notify(1);
+                Ok(msg)
+            }
+            Err(PopError::Empty) => Err(TryRecvError::Empty),
+            Err(PopError::Closed) => Err(TryRecvError::Closed),
+        }
+    }
+
+    /// Receives a message from the channel.
+    ///
+    /// If the channel is empty, this method waits until there is a message, or until the
+    /// channel is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::{unbounded, RecvError};
+    /// use futures_lite::future::block_on;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// block_on(async {
+    ///     assert_eq!(s.send(1).await, Ok(()));
+    ///     drop(s);
+    ///     assert_eq!(r.recv().await, Ok(1));
+    ///     assert_eq!(r.recv().await, Err(RecvError));
+    /// });
+    /// ```
+    pub fn recv(&self) -> Recv<'_, T> {
+        Recv {
+            receiver: self,
+            listener: None,
+            parked: false,
+        }
+    }
+
+    /// Receives a message from the channel, without borrowing the receiver.
+    ///
+    /// This clones the receiver handle internally, which is handy when the future must be
+    /// `'static` (for example, when it has to be spawned onto an executor).
+    pub fn recv_owned(&self) -> RecvOwned<T> {
+        RecvOwned {
+            receiver: self.clone(),
+            listener: None,
+            parked: false,
+        }
+    }
+
+    /// Receives a message from the channel, blocking the current thread until one is received.
+    ///
+    /// This method should not be used in an async context.
+    #[cfg(feature = "std")]
+    pub fn recv_blocking(&self) -> Result<T, RecvError> {
+        future::block_on(self.recv())
+    }
+
+    /// Receives a message from the channel, waiting at most `timeout` for one to arrive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use async_channel::{unbounded, RecvTimeoutError};
+    /// use futures_lite::future::block_on;
+    ///
+    /// let (_s, r) = unbounded::<()>();
+    ///
+    /// block_on(async {
+    ///     assert_eq!(
+    ///         r.recv_timeout(Duration::from_millis(1)).await,
+    ///         Err(RecvTimeoutError::Timeout),
+    ///     );
+    /// });
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn recv_timeout(&self, timeout: Duration) -> RecvTimeout<'_, T> {
+        RecvTimeout {
+            receiver: self,
+            listener: None,
+            timer: None,
+            deadline: Instant::now() + timeout,
+            parked: false,
+        }
+    }
+
+    /// Receives a message from the channel, blocking the current thread for at most `timeout`
+    /// waiting for one to arrive.
+    ///
+    /// This method should not be used in an async context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use async_channel::{unbounded, RecvTimeoutError};
+    ///
+    /// let (_s, r) = unbounded::<()>();
+    ///
+    /// assert_eq!(
+    ///     r.recv_timeout_blocking(Duration::from_millis(1)),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn recv_timeout_blocking(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut listener = None;
+        let mut parked = false;
+
+        loop {
+            match self.channel.pop() {
+                Ok(msg) => {
+                    if parked {
+                        self.channel.unmark_receiver_waiting();
+                    }
+                    self.channel.send_ops.notify(1);
+                    return Ok(msg);
+                }
+                Err(PopError::Closed) => {
+                    if parked {
+                        self.channel.unmark_receiver_waiting();
+                    }
+                    return Err(RecvTimeoutError::Closed);
+                }
+                Err(PopError::Empty) => {}
+            }
+
+            if !parked {
+                self.channel.mark_receiver_waiting();
+                parked = true;
+            }
+
+            match listener.take() {
+                // A listener registered just now might have missed a notification that fired in
+                // the gap between the `pop()` above and `listen()` below, so loop back and retry
+                // `pop()` before ever waiting on it — mirrors how `poll_recv` only waits on a
+                // listener from a previous iteration, never one it just created.
+                None => listener = Some(self.channel.recv_ops.listen()),
+                Some(l) => {
+                    // `wait_deadline` returning `None` means the deadline passed; dropping `l`
+                    // here deregisters it from the waiter list so a later send doesn't wake a
+                    // thread that already gave up.
+                    if l.wait_deadline(deadline).is_none() {
+                        if parked {
+                            self.channel.unmark_receiver_waiting();
+                        }
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a blocking iterator over messages in the channel.
+    ///
+    /// Each call to [`Iterator::next()`] blocks the current thread until a message is received
+    /// or the channel is closed and drained, in which case it returns `None`.
+    ///
+    /// This method should not be used in an async context.
+    #[cfg(feature = "std")]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { receiver: self }
+    }
+
+    /// Returns an iterator over messages that are immediately available in the channel.
+    ///
+    /// The iterator stops as soon as the channel is empty, without waiting for more messages to
+    /// arrive, even if the channel isn't closed.
+    #[cfg(feature = "std")]
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { receiver: self }
+    }
+
+    /// Closes the channel.
+    ///
+    /// Returns `true` if this call closed the channel, or `false` if it was already closed.
+    pub fn close(&self) -> bool {
+        self.channel.close()
+    }
+
+    /// Returns `true` if the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.channel.is_closed()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.channel.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.channel.is_full()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.channel.len()
+    }
+
+    /// Returns the channel capacity, if any.
+    pub fn capacity(&self) -> Option<usize> {
+        self.channel.capacity()
+    }
+
+    /// Returns the number of receivers for the channel.
+    pub fn receiver_count(&self) -> usize {
+        self.channel.receiver_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of senders for the channel.
+    pub fn sender_count(&self) -> usize {
+        self.channel.sender_count.load(Ordering::SeqCst)
+    }
+
+    /// Downgrades the receiver to a [`WeakReceiver`].
+    #[cfg(feature = "std")]
+    pub fn downgrade(&self) -> WeakReceiver<T> {
+        WeakReceiver {
+            channel: Arc::downgrade(&self.channel),
+        }
+    }
+
+    /// Returns `true` if senders/receivers belong to the same channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::unbounded;
+    ///
+    /// let (_s, r) = unbounded::<()>();
+    /// let (_s2, r2) = unbounded::<()>();
+    ///
+    /// assert!(r.same_channel(&r.clone()));
+    /// assert!(!r.same_channel(&r2));
+    /// ```
+    pub fn same_channel(&self, other: &Receiver<T>) -> bool {
+        Arc::ptr_eq(&self.channel, &other.channel)
+    }
+}
+
+impl<T> Unpin for Receiver<T> {}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if self.stream_parked {
+            self.channel.unmark_receiver_waiting();
+        }
+        if self.channel.receiver_count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.channel.close();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Receiver { .. }")
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        let count = self.channel.receiver_count.fetch_add(1, Ordering::Relaxed);
+        if count > usize::MAX / 2 {
+            std::process::abort();
+        }
+        Receiver {
+            channel: self.channel.clone(),
+            stream_listener: None,
+            stream_parked: false,
+        }
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        poll_recv(
+            &this.channel,
+            &this.channel.stream_ops,
+            &mut this.stream_listener,
+            &mut this.stream_parked,
+            cx,
+        )
+        .map(Result::ok)
+    }
+}
+
+/// A blocking iterator over messages in a [`Receiver`], created by [`Receiver::iter()`].
+#[cfg(feature = "std")]
+pub struct Iter<'a, T> {
+    receiver: &'a Receiver<T>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Iter { .. }")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv_blocking().ok()
+    }
+}
+
+/// A non-blocking iterator over messages in a [`Receiver`], created by [`Receiver::try_iter()`].
+#[cfg(feature = "std")]
+pub struct TryIter<'a, T> {
+    receiver: &'a Receiver<T>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> fmt::Debug for TryIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TryIter { .. }")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Iterator for TryIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// A blocking iterator over messages in a [`Receiver`], created by its [`IntoIterator`] impl.
+#[cfg(feature = "std")]
+pub struct IntoIter<T> {
+    receiver: Receiver<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for IntoIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IntoIter { .. }")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv_blocking().ok()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { receiver: self }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// A weak reference to a [`Sender`].
+///
+/// Unlike a [`Sender`], a `WeakSender` does not keep the channel open: the channel closes as
+/// soon as every strong [`Sender`] is dropped, regardless of how many `WeakSender`s remain.
+#[cfg(feature = "std")]
+pub struct WeakSender<T> {
+    channel: Weak<Channel<T>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> WeakSender<T> {
+    /// Upgrades the `WeakSender` to a [`Sender`], if the channel still has at least one sender.
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        let channel = self.channel.upgrade()?;
+        // Only hand out a new strong sender if there is still at least one alive; otherwise the
+        // channel has already run its closing logic and we shouldn't resurrect it.
+        if channel.sender_count.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+        channel.sender_count.fetch_add(1, Ordering::SeqCst);
+        Some(Sender { channel })
+    }
+
+    /// Returns `true` if both weak senders point to the same channel allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::unbounded;
+    ///
+    /// let (s, _r) = unbounded::<()>();
+    /// let (s2, _r2) = unbounded::<()>();
+    /// let weak_s = s.downgrade();
+    ///
+    /// assert!(weak_s.same_channel(&weak_s.clone()));
+    /// assert!(!weak_s.same_channel(&s2.downgrade()));
+    /// ```
+    pub fn same_channel(&self, other: &WeakSender<T>) -> bool {
+        Weak::ptr_eq(&self.channel, &other.channel)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        WeakSender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for WeakSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WeakSender { .. }")
+    }
+}
+
+/// A weak reference to a [`Receiver`].
+///
+/// Unlike a [`Receiver`], a `WeakReceiver` does not keep the channel open: the channel closes as
+/// soon as every strong [`Receiver`] is dropped, regardless of how many `WeakReceiver`s remain.
+#[cfg(feature = "std")]
+pub struct WeakReceiver<T> {
+    channel: Weak<Channel<T>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> WeakReceiver<T> {
+    /// Upgrades the `WeakReceiver` to a [`Receiver`], if the channel still has at least one
+    /// receiver.
+    pub fn upgrade(&self) -> Option<Receiver<T>> {
+        let channel = self.channel.upgrade()?;
+        if channel.receiver_count.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+        channel.receiver_count.fetch_add(1, Ordering::SeqCst);
+        Some(Receiver {
+            channel,
+            stream_listener: None,
+            stream_parked: false,
+        })
+    }
+
+    /// Returns `true` if both weak receivers point to the same channel allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_channel::unbounded;
+    ///
+    /// let (_s, r) = unbounded::<()>();
+    /// let (_s2, r2) = unbounded::<()>();
+    /// let weak_r = r.downgrade();
+    ///
+    /// assert!(weak_r.same_channel(&weak_r.clone()));
+    /// assert!(!weak_r.same_channel(&r2.downgrade()));
+    /// ```
+    pub fn same_channel(&self, other: &WeakReceiver<T>) -> bool {
+        Weak::ptr_eq(&self.channel, &other.channel)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Clone for WeakReceiver<T> {
+    fn clone(&self) -> Self {
+        WeakReceiver {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for WeakReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WeakReceiver { .. }")
+    }
+}
+
+/// Polls a receive operation on a channel, registering `listener`/`parked` bookkeeping as
+/// needed. Shared by [`Recv`], [`RecvOwned`] and the [`Stream`] implementation.
+fn poll_recv<T>(
+    channel: &Channel<T>,
+    event: &Event,
+    listener: &mut Option<EventListener>,
+    parked: &mut bool,
+    cx: &mut Context<'_>,
+) -> Poll<Result<T, RecvError>> {
+    loop {
+        match channel.pop() {
+            Ok(msg) => {
+                if *parked {
+                    channel.unmark_receiver_waiting();
+                    *parked = false;
+                }
+                channel.send_ops.notify(1);
+                return Poll::Ready(Ok(msg));
+            }
+            Err(PopError::Closed) => {
+                if *parked {
+                    channel.unmark_receiver_waiting();
+                    *parked = false;
+                }
+                return Poll::Ready(Err(RecvError));
+            }
+            Err(PopError::Empty) => {}
+        }
+
+        if !*parked {
+            channel.mark_receiver_waiting();
+            *parked = true;
+        }
+
+        match listener.take() {
+            None => *listener = Some(event.listen()),
+            Some(mut l) => {
+                if Pin::new(&mut l).poll(cx).is_pending() {
+                    *listener = Some(l);
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+/// A future returned by [`Sender::send()`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Send<'a, T> {
+    sender: &'a Sender<T>,
+    listener: Option<EventListener>,
+    msg: Option<T>,
+}
+
+impl<'a, T> Unpin for Send<'a, T> {}
+
+impl<'a, T> fmt::Debug for Send<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Send { .. }")
+    }
+}
+
+impl<'a, T> Future for Send<'a, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let msg = self.msg.take().expect("polled `Send` after completion");
+            match self.sender.try_send(msg) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(TrySendError::Closed(msg)) => return Poll::Ready(Err(SendError(msg))),
+                Err(TrySendError::Full(msg)) => self.msg = Some(msg),
+            }
+
+            match self.listener.take() {
+                None => {
+                    self.listener = Some(self.sender.channel.send_ops.listen());
+                }
+                Some(mut l) => {
+                    if Pin::new(&mut l).poll(cx).is_pending() {
+                        self.listener = Some(l);
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A future returned by [`Receiver::recv()`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Recv<'a, T> {
+    receiver: &'a Receiver<T>,
+    listener: Option<EventListener>,
+    parked: bool,
+}
+
+impl<'a, T> Unpin for Recv<'a, T> {}
+
+impl<'a, T> fmt::Debug for Recv<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Recv { .. }")
+    }
+}
+
+impl<'a, T> Future for Recv<'a, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        poll_recv(
+            &this.receiver.channel,
+            &this.receiver.channel.recv_ops,
+            &mut this.listener,
+            &mut this.parked,
+            cx,
+        )
+    }
+}
+
+impl<'a, T> Drop for Recv<'a, T> {
+    fn drop(&mut self) {
+        if self.parked {
+            self.receiver.channel.unmark_receiver_waiting();
+        }
+    }
+}
+
+/// A future returned by [`Receiver::recv_owned()`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvOwned<T> {
+    receiver: Receiver<T>,
+    listener: Option<EventListener>,
+    parked: bool,
+}
+
+impl<T> Unpin for RecvOwned<T> {}
+
+impl<T> fmt::Debug for RecvOwned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecvOwned { .. }")
+    }
+}
+
+impl<T> Future for RecvOwned<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        poll_recv(
+            &this.receiver.channel,
+            &this.receiver.channel.recv_ops,
+            &mut this.listener,
+            &mut this.parked,
+            cx,
+        )
+    }
+}
+
+impl<T> Drop for RecvOwned<T> {
+    fn drop(&mut self) {
+        if self.parked {
+            self.receiver.channel.unmark_receiver_waiting();
+        }
+    }
+}
+
+/// Polls a receive operation bounded by `deadline`, racing the channel's `recv_ops` listener
+/// against a timer that fires once the deadline passes.
+#[cfg(feature = "std")]
+fn poll_recv_timeout<T>(
+    channel: &Channel<T>,
+    listener: &mut Option<EventListener>,
+    timer: &mut Option<EventListener>,
+    parked: &mut bool,
+    deadline: Instant,
+    cx: &mut Context<'_>,
+) -> Poll<Result<T, RecvTimeoutError>> {
+    loop {
+        match channel.pop() {
+            Ok(msg) => {
+                if *parked {
+                    channel.unmark_receiver_waiting();
+                    *parked = false;
+                }
+                channel.send_ops.notify(1);
+                return Poll::Ready(Ok(msg));
+            }
+            Err(PopError::Closed) => {
+                if *parked {
+                    channel.unmark_receiver_waiting();
+                    *parked = false;
+                }
+                return Poll::Ready(Err(RecvTimeoutError::Closed));
+            }
+            Err(PopError::Empty) => {}
+        }
+
+        if Instant::now() >= deadline {
+            if *parked {
+                channel.unmark_receiver_waiting();
+                *parked = false;
+            }
+            return Poll::Ready(Err(RecvTimeoutError::Timeout));
+        }
+
+        if !*parked {
+            channel.mark_receiver_waiting();
+            *parked = true;
+        }
+
+        let listener_is_new = listener.is_none();
+        if listener_is_new {
+            *listener = Some(channel.recv_ops.listen());
+        }
+        let timer_is_new = timer.is_none();
+        if timer_is_new {
+            *timer = Some(timer::deadline_listener(deadline));
+        }
+
+        if listener_is_new || timer_is_new {
+            // A listener created just now might have missed a notification that fired in the gap
+            // before it existed, so loop back and recheck the channel/deadline before ever
+            // polling it — mirrors `poll_recv`'s handling of a freshly-created listener.
+            continue;
+        }
+
+        let recv_woken = Pin::new(listener.as_mut().unwrap()).poll(cx).is_ready();
+        if recv_woken {
+            *listener = None;
+        }
+        let timer_fired = Pin::new(timer.as_mut().unwrap()).poll(cx).is_ready();
+        if timer_fired {
+            *timer = None;
+        }
+
+        if !recv_woken && !timer_fired {
+            return Poll::Pending;
+        }
+        // Either the channel changed or the deadline passed (or both) — loop around to recheck.
+    }
+}
+
+/// A future returned by [`Receiver::recv_timeout()`].
+#[cfg(feature = "std")]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvTimeout<'a, T> {
+    receiver: &'a Receiver<T>,
+    listener: Option<EventListener>,
+    timer: Option<EventListener>,
+    deadline: Instant,
+    parked: bool,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Unpin for RecvTimeout<'a, T> {}
+
+#[cfg(feature = "std")]
+impl<'a, T> fmt::Debug for RecvTimeout<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecvTimeout { .. }")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Future for RecvTimeout<'a, T> {
+    type Output = Result<T, RecvTimeoutError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        poll_recv_timeout(
+            &this.receiver.channel,
+            &mut this.listener,
+            &mut this.timer,
+            &mut this.parked,
+            this.deadline,
+            cx,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Drop for RecvTimeout<'a, T> {
+    fn drop(&mut self) {
+        if self.parked {
+            self.receiver.channel.unmark_receiver_waiting();
+        }
+    }
+}
+
+/// A future returned by [`Sender::send_timeout()`].
+#[cfg(feature = "std")]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SendTimeout<'a, T> {
+    sender: &'a Sender<T>,
+    listener: Option<EventListener>,
+    timer: Option<EventListener>,
+    deadline: Instant,
+    msg: Option<T>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Unpin for SendTimeout<'a, T> {}
+
+#[cfg(feature = "std")]
+impl<'a, T> fmt::Debug for SendTimeout<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SendTimeout { .. }")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> Future for SendTimeout<'a, T> {
+    type Output = Result<(), SendTimeoutError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let msg = self.msg.take().expect("polled `SendTimeout` after completion");
+            match self.sender.try_send(msg) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(TrySendError::Closed(msg)) => {
+                    return Poll::Ready(Err(SendTimeoutError::Closed(msg)))
+                }
+                Err(TrySendError::Full(msg)) => self.msg = Some(msg),
+            }
+
+            if Instant::now() >= self.deadline {
+                let msg = self.msg.take().expect("polled `SendTimeout` after completion");
+                return Poll::Ready(Err(SendTimeoutError::Timeout(msg)));
+            }
+
+            let listener_is_new = self.listener.is_none();
+            if listener_is_new {
+                self.listener = Some(self.sender.channel.send_ops.listen());
+            }
+            let timer_is_new = self.timer.is_none();
+            if timer_is_new {
+                self.timer = Some(timer::deadline_listener(self.deadline));
+            }
+
+            if listener_is_new || timer_is_new {
+                // A listener created just now might have missed a notification that fired in the
+                // gap before it existed, so loop back and recheck try_send/the deadline before
+                // ever polling it — mirrors `poll_recv`'s handling of a freshly-created listener.
+                continue;
+            }
+
+            let send_woken = Pin::new(self.listener.as_mut().unwrap()).poll(cx).is_ready();
+            if send_woken {
+                self.listener = None;
+            }
+            let timer_fired = Pin::new(self.timer.as_mut().unwrap()).poll(cx).is_ready();
+            if timer_fired {
+                self.timer = None;
+            }
+
+            if !send_woken && !timer_fired {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+/// An error returned from [`Sender::send()`].
+///
+/// Received because the channel is closed.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> SendError<T> {
+    /// Unwraps the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SendError(..)")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending into a closed channel")
+    }
+}
+
+impl<T> error::Error for SendError<T> {}
+
+/// An error returned from [`Sender::try_send()`].
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// The channel is full, or (for a rendezvous channel) no receiver is ready to take the
+    /// message directly.
+    Full(T),
+
+    /// The channel is closed.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Unwraps the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(t) => t,
+            TrySendError::Closed(t) => t,
+        }
+    }
+
+    /// Returns `true` if the channel is full but not closed.
+    pub fn is_full(&self) -> bool {
+        matches!(self, TrySendError::Full(_))
+    }
+
+    /// Returns `true` if the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TrySendError::Closed(_))
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => write!(f, "Full(..)"),
+            TrySendError::Closed(..) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => write!(f, "sending into a full channel"),
+            TrySendError::Closed(..) => write!(f, "sending into a closed channel"),
+        }
+    }
+}
+
+impl<T> error::Error for TrySendError<T> {}
+
+/// An error returned from [`Receiver::recv()`].
+///
+/// Received because the channel is empty and closed.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiving from an empty and closed channel")
+    }
+}
+
+impl error::Error for RecvError {}
+
+/// An error returned from [`Receiver::try_recv()`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TryRecvError {
+    /// The channel is empty but not closed.
+    Empty,
+
+    /// The channel is empty and closed.
+    Closed,
+}
+
+impl TryRecvError {
+    /// Returns `true` if the channel is empty but not closed.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, TryRecvError::Empty)
+    }
+
+    /// Returns `true` if the channel is empty and closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TryRecvError::Closed)
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving from an empty channel"),
+            TryRecvError::Closed => write!(f, "receiving from an empty and closed channel"),
+        }
+    }
+}
+
+impl error::Error for TryRecvError {}
+
+/// An error returned from [`Receiver::recv_timeout()`] and
+/// [`Receiver::recv_timeout_blocking()`].
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvTimeoutError {
+    /// The timeout elapsed before a message arrived.
+    Timeout,
+
+    /// The channel is empty and closed.
+    Closed,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on an empty channel"),
+            RecvTimeoutError::Closed => write!(f, "receiving from an empty and closed channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for RecvTimeoutError {}
+
+/// An error returned from [`Sender::send_timeout()`] and [`Sender::send_timeout_blocking()`].
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendTimeoutError<T> {
+    /// The timeout elapsed before space became available.
+    Timeout(T),
+
+    /// The channel is closed.
+    Closed(T),
+}
+
+#[cfg(feature = "std")]
+impl<T> SendTimeoutError<T> {
+    /// Unwraps the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendTimeoutError::Timeout(t) => t,
+            SendTimeoutError::Closed(t) => t,
+        }
+    }
+
+    /// Returns `true` if the deadline elapsed before space became available.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, SendTimeoutError::Timeout(_))
+    }
+
+    /// Returns `true` if the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, SendTimeoutError::Closed(_))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(..) => write!(f, "Timeout(..)"),
+            SendTimeoutError::Closed(..) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(..) => write!(f, "timed out waiting on a full channel"),
+            SendTimeoutError::Closed(..) => write!(f, "sending into a closed channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> error::Error for SendTimeoutError<T> {}
@@ -0,0 +1,257 @@
+//! A `Select`-style subsystem for racing multiple channel operations, mirroring
+//! `crossbeam-channel`'s `Select` builder.
+//!
+//! [`Select`] registers interest across several [`Receiver`]/[`Sender`] handles at once and
+//! resolves as soon as any one of them can make progress, handing back a [`SelectedOperation`]
+//! that identifies which one and lets the caller finish it.
+
+use std::cell::Cell;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use event_listener::{Event, EventListener};
+
+use crate::{Receiver, RecvError, SendError, Sender};
+
+/// A handle that can participate in a [`Select`].
+trait SelectHandle {
+    /// Returns `true` if this operation could complete right now.
+    fn is_ready(&self) -> bool;
+
+    /// The event to listen on while waiting for this operation to become ready.
+    fn event(&self) -> &Event;
+
+    /// Marks that a task is now waiting specifically on this operation (used so that
+    /// zero-capacity rendezvous channels can find a parked receiver).
+    fn mark_waiting(&self) {}
+
+    /// Undoes a previous [`SelectHandle::mark_waiting()`].
+    fn unmark_waiting(&self) {}
+}
+
+impl<T> SelectHandle for Receiver<T> {
+    fn is_ready(&self) -> bool {
+        self.channel.recv_ready()
+    }
+
+    fn event(&self) -> &Event {
+        &self.channel.recv_ops
+    }
+
+    fn mark_waiting(&self) {
+        self.channel.mark_receiver_waiting();
+    }
+
+    fn unmark_waiting(&self) {
+        self.channel.unmark_receiver_waiting();
+    }
+}
+
+impl<T> SelectHandle for Sender<T> {
+    fn is_ready(&self) -> bool {
+        self.channel.send_ready()
+    }
+
+    fn event(&self) -> &Event {
+        &self.channel.send_ops
+    }
+}
+
+/// A builder for waiting on a number of channel operations at once.
+///
+/// Add operations with [`Select::recv()`] and [`Select::send()`], then wait for the first one
+/// that can complete with [`Select::select()`] (or poll immediately with
+/// [`Select::try_select()`]). Successive selections rotate through the registered operations in
+/// round-robin order, so a branch that is ready on every call isn't able to starve the others.
+///
+/// # Examples
+///
+/// ```
+/// use async_channel::{unbounded, Select};
+/// use futures_lite::future::block_on;
+///
+/// let (s1, r1) = unbounded::<&str>();
+/// let (_s2, r2) = unbounded::<&str>();
+/// s1.try_send("hello").unwrap();
+///
+/// block_on(async {
+///     let mut select = Select::new();
+///     select.recv(&r1);
+///     select.recv(&r2);
+///
+///     let op = select.select().await;
+///     assert_eq!(op.index(), 0);
+///     assert_eq!(op.recv(&r1).await, Ok("hello"));
+/// });
+/// ```
+pub struct Select<'a> {
+    handles: Vec<&'a dyn SelectHandle>,
+    start: Cell<usize>,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty `Select`.
+    pub fn new() -> Self {
+        Select {
+            handles: Vec::new(),
+            start: Cell::new(0),
+        }
+    }
+
+    /// Adds a receive operation on `r`.
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> &mut Self {
+        self.handles.push(r);
+        self
+    }
+
+    /// Adds a send operation on `s`.
+    pub fn send<T>(&mut self, s: &'a Sender<T>) -> &mut Self {
+        self.handles.push(s);
+        self
+    }
+
+    /// Returns an operation that is ready right now, if any, without waiting.
+    ///
+    /// Advances the starting point used for the next call, so that repeated calls don't always
+    /// favor the earliest-registered operation.
+    pub fn try_select(&self) -> Option<SelectedOperation<'a>> {
+        let len = self.handles.len();
+        if len == 0 {
+            return None;
+        }
+        let start = self.start.get() % len;
+        for offset in 0..len {
+            let index = (start + offset) % len;
+            if self.handles[index].is_ready() {
+                self.start.set((index + 1) % len);
+                return Some(SelectedOperation {
+                    index,
+                    _marker: PhantomData,
+                });
+            }
+        }
+        None
+    }
+
+    /// Waits until one of the registered operations can complete.
+    pub fn select(&self) -> SelectFuture<'a, '_> {
+        let len = self.handles.len();
+        SelectFuture {
+            select: self,
+            listeners: (0..len).map(|_| None).collect(),
+            parked: vec![false; len],
+        }
+    }
+}
+
+impl<'a> Default for Select<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> fmt::Debug for Select<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Select { .. }")
+    }
+}
+
+/// A future returned by [`Select::select()`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SelectFuture<'a, 'b> {
+    select: &'b Select<'a>,
+    listeners: Vec<Option<EventListener>>,
+    parked: Vec<bool>,
+}
+
+impl<'a, 'b> Unpin for SelectFuture<'a, 'b> {}
+
+impl<'a, 'b> fmt::Debug for SelectFuture<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SelectFuture { .. }")
+    }
+}
+
+impl<'a, 'b> Future for SelectFuture<'a, 'b> {
+    type Output = SelectedOperation<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        loop {
+            if let Some(op) = this.select.try_select() {
+                return Poll::Ready(op);
+            }
+
+            let mut progressed = false;
+            for (index, handle) in this.select.handles.iter().enumerate() {
+                if !this.parked[index] {
+                    handle.mark_waiting();
+                    this.parked[index] = true;
+                }
+                if this.listeners[index].is_none() {
+                    this.listeners[index] = Some(handle.event().listen());
+                }
+                let listener = this.listeners[index].as_mut().unwrap();
+                if Pin::new(listener).poll(cx).is_ready() {
+                    this.listeners[index] = None;
+                    progressed = true;
+                }
+            }
+
+            if !progressed {
+                return Poll::Pending;
+            }
+            // At least one registered event fired; loop around and recheck `try_select`.
+        }
+    }
+}
+
+impl<'a, 'b> Drop for SelectFuture<'a, 'b> {
+    fn drop(&mut self) {
+        for (index, handle) in self.select.handles.iter().enumerate() {
+            if self.parked[index] {
+                handle.unmark_waiting();
+            }
+        }
+    }
+}
+
+/// The operation selected by [`Select::select()`] or [`Select::try_select()`].
+///
+/// Finish it by calling [`SelectedOperation::recv()`] or [`SelectedOperation::send()`] with the
+/// same handle that was registered at [`SelectedOperation::index()`].
+#[must_use = "a `SelectedOperation` does nothing until you finish it with `.recv()` or `.send()`"]
+pub struct SelectedOperation<'a> {
+    index: usize,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> SelectedOperation<'a> {
+    /// Returns the index of the selected operation, in the order it was registered.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the operation as a receive on `r`.
+    ///
+    /// `r` must be the same receiver that was registered at [`SelectedOperation::index()`].
+    pub async fn recv<T>(self, r: &Receiver<T>) -> Result<T, RecvError> {
+        r.recv().await
+    }
+
+    /// Completes the operation as a send of `msg` on `s`.
+    ///
+    /// `s` must be the same sender that was registered at [`SelectedOperation::index()`].
+    pub async fn send<T>(self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        s.send(msg).await
+    }
+}
+
+impl<'a> fmt::Debug for SelectedOperation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SelectedOperation { .. }")
+    }
+}
@@ -0,0 +1,28 @@
+//! Deadline-based wakeups, used to implement the timeout operations without pulling in an
+//! external reactor.
+//!
+//! There is no portable, dependency-free way to be woken up after a delay other than blocking a
+//! thread, so [`deadline_listener()`] spins up a one-shot helper thread that sleeps until the
+//! deadline and then fires an [`Event`]. This keeps [`crate::Receiver::recv_timeout()`] and
+//! friends usable on any executor, at the cost of one thread per in-flight timeout.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use event_listener::{Event, EventListener};
+
+/// Returns a listener that is notified once `deadline` has passed.
+pub(crate) fn deadline_listener(deadline: Instant) -> EventListener {
+    let event = Arc::new(Event::new());
+    let listener = event.listen();
+
+    thread::spawn(move || {
+        if let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            thread::sleep(remaining);
+        }
+        event.notify(usize::MAX);
+    });
+
+    listener
+}
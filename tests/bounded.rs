@@ -0,0 +1,162 @@
+#![allow(clippy::bool_assert_comparison, unused_imports)]
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_channel::{after, bounded, RecvError, SendError, Select, TryRecvError, TrySendError};
+use easy_parallel::Parallel;
+use futures_lite::{future, prelude::*};
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[cfg(not(target_family = "wasm"))]
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded(1);
+
+    s.try_send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    future::block_on(s.send(8)).unwrap();
+    assert_eq!(future::block_on(r.recv()), Ok(8));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn capacity() {
+    let (s, r) = bounded::<()>(5);
+    assert_eq!(s.capacity(), Some(5));
+    assert_eq!(r.capacity(), Some(5));
+}
+
+#[test]
+fn full() {
+    let (s, _r) = bounded(1);
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+}
+
+#[test]
+fn zero_capacity_try_send_requires_parked_receiver() {
+    let (s, r) = bounded(0);
+
+    assert_eq!(s.is_full(), true);
+    assert_eq!(r.is_full(), true);
+    assert_eq!(r.is_empty(), true);
+
+    // No receiver is waiting yet, so a handoff can't happen.
+    assert_eq!(s.try_send(1), Err(TrySendError::Full(1)));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn zero_capacity_rendezvous_handoff() {
+    let (s, r) = bounded(0);
+
+    Parallel::new()
+        .add(move || {
+            assert_eq!(future::block_on(r.recv()), Ok(7));
+        })
+        .add(move || {
+            // Give the receiver time to park before sending, so the handoff actually has to
+            // wait on `waiting_receivers` rather than happening to win a race.
+            sleep(ms(500));
+            future::block_on(s.send(7)).unwrap();
+        })
+        .run();
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn zero_capacity_try_send_after_receiver_parks() {
+    let (s, r) = bounded(0);
+
+    Parallel::new()
+        .add(move || {
+            sleep(ms(500));
+            // By now the other thread should be parked waiting, so a direct handoff succeeds.
+            assert_eq!(s.try_send(7), Ok(()));
+        })
+        .add(move || {
+            assert_eq!(future::block_on(r.recv()), Ok(7));
+        })
+        .run();
+}
+
+#[test]
+fn send_after_close() {
+    let (s, r) = bounded(1);
+
+    future::block_on(s.send(1)).unwrap();
+    drop(r);
+
+    assert_eq!(future::block_on(s.send(4)), Err(SendError(4)));
+    assert_eq!(s.try_send(5), Err(TrySendError::Closed(5)));
+}
+
+// The rendezvous handoff isn't tied to a specific parked receiver, only to the fact that one is
+// waiting (see the caveat documented on `bounded()`/`Rendezvous`): if a `Select` waiting on the
+// receiver caused a `send()` to complete but then resolved on a timeout instead, the message is
+// left in the slot and handed to
+// whichever `recv()` call comes next instead. This pins down the resulting contract: every value
+// the sender hands off eventually turns up exactly once, even though some individual races below
+// are deliberately lost to a very short timeout.
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn rendezvous_cancelled_recv_does_not_lose_the_message() {
+    const COUNT: usize = 20;
+
+    let (s, r) = bounded(0);
+
+    Parallel::new()
+        .add(move || {
+            for i in 0..COUNT {
+                future::block_on(s.send(i)).unwrap();
+            }
+        })
+        .add(move || {
+            let mut received = Vec::new();
+            while received.len() < COUNT {
+                let mut select = Select::new();
+                select.recv(&r);
+                let timeout = after(Duration::from_micros(100));
+                select.recv(&timeout);
+
+                let op = future::block_on(select.select());
+                if op.index() == 0 {
+                    if let Ok(n) = future::block_on(op.recv(&r)) {
+                        received.push(n);
+                    }
+                }
+                // index 1: the timeout won the race, and the select's waiting registration on `r`
+                // (made through `mark_receiver_waiting()`) was dropped when `select()` resolved.
+                // Any message the sender already placed in the slot isn't lost — the next loop
+                // iteration picks it up instead.
+            }
+            received.sort_unstable();
+            assert_eq!(received, (0..COUNT).collect::<Vec<_>>());
+        })
+        .run();
+}
+
+#[test]
+fn recv_after_close() {
+    let (s, r) = bounded(3);
+
+    future::block_on(s.send(1)).unwrap();
+    future::block_on(s.send(2)).unwrap();
+    future::block_on(s.send(3)).unwrap();
+
+    drop(s);
+
+    assert_eq!(future::block_on(r.recv()), Ok(1));
+    assert_eq!(future::block_on(r.recv()), Ok(2));
+    assert_eq!(future::block_on(r.recv()), Ok(3));
+    assert_eq!(future::block_on(r.recv()), Err(RecvError));
+}
@@ -0,0 +1,111 @@
+#![allow(clippy::bool_assert_comparison, unused_imports)]
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_channel::{bounded, unbounded, Select};
+use easy_parallel::Parallel;
+use futures_lite::future;
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[cfg(not(target_family = "wasm"))]
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn try_select_picks_ready_receiver() {
+    let (s1, r1) = unbounded::<&str>();
+    let (_s2, r2) = unbounded::<&str>();
+    s1.try_send("hello").unwrap();
+
+    let mut select = Select::new();
+    select.recv(&r1);
+    select.recv(&r2);
+
+    let op = select.try_select().unwrap();
+    assert_eq!(op.index(), 0);
+    assert_eq!(future::block_on(op.recv(&r1)), Ok("hello"));
+}
+
+#[test]
+fn try_select_returns_none_when_nothing_ready() {
+    let (_s1, r1) = unbounded::<()>();
+    let (_s2, r2) = unbounded::<()>();
+
+    let mut select = Select::new();
+    select.recv(&r1);
+    select.recv(&r2);
+
+    assert!(select.try_select().is_none());
+}
+
+#[test]
+fn select_rotates_round_robin() {
+    let (s1, r1) = unbounded::<&str>();
+    let (s2, r2) = unbounded::<&str>();
+    s1.try_send("a").unwrap();
+    s2.try_send("b").unwrap();
+
+    let mut select = Select::new();
+    select.recv(&r1);
+    select.recv(&r2);
+
+    // Both are ready; the first call favors index 0. Index 0 is then refilled so both are ready
+    // again, and the second call still picks index 1, since fairness rotates the starting point.
+    let first = select.try_select().unwrap();
+    assert_eq!(first.index(), 0);
+    assert_eq!(future::block_on(first.recv(&r1)), Ok("a"));
+
+    s1.try_send("a2").unwrap();
+    let second = select.try_select().unwrap();
+    assert_eq!(second.index(), 1);
+    assert_eq!(future::block_on(second.recv(&r2)), Ok("b"));
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn select_waits_for_a_send() {
+    let (s, r) = unbounded::<&str>();
+
+    Parallel::new()
+        .add(move || {
+            let mut select = Select::new();
+            select.recv(&r);
+
+            let op = future::block_on(select.select());
+            assert_eq!(op.index(), 0);
+            assert_eq!(future::block_on(op.recv(&r)), Ok("hi"));
+        })
+        .add(move || {
+            sleep(ms(500));
+            s.try_send("hi").unwrap();
+        })
+        .run();
+}
+
+// A zero-capacity rendezvous receiver should be selectable as soon as a sender has a message
+// waiting in the handoff slot, not permanently unready (the bug fixed alongside this test: a
+// naive `!is_empty()` readiness check can never be true for a rendezvous channel).
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn select_sees_ready_rendezvous_receiver() {
+    let (s, r) = bounded::<&str>(0);
+
+    Parallel::new()
+        .add(move || {
+            let mut select = Select::new();
+            select.recv(&r);
+
+            let op = future::block_on(select.select());
+            assert_eq!(op.index(), 0);
+            assert_eq!(future::block_on(op.recv(&r)), Ok("hi"));
+        })
+        .add(move || {
+            sleep(ms(500));
+            future::block_on(s.send("hi")).unwrap();
+        })
+        .run();
+}
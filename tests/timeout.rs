@@ -0,0 +1,179 @@
+#![allow(clippy::bool_assert_comparison, unused_imports)]
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_channel::{bounded, unbounded, RecvTimeoutError, SendTimeoutError};
+use easy_parallel::Parallel;
+use futures_lite::future;
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[cfg(not(target_family = "wasm"))]
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn recv_timeout_elapses_on_empty_channel() {
+    let (_s, r) = unbounded::<()>();
+
+    assert_eq!(
+        future::block_on(r.recv_timeout(Duration::from_millis(1))),
+        Err(RecvTimeoutError::Timeout),
+    );
+}
+
+#[test]
+fn recv_timeout_succeeds_before_deadline() {
+    let (s, r) = unbounded();
+
+    s.try_send(7).unwrap();
+    assert_eq!(future::block_on(r.recv_timeout(ms(1000))), Ok(7));
+}
+
+#[test]
+fn send_timeout_elapses_on_full_channel() {
+    let (s, _r) = bounded(1);
+
+    s.try_send(1).unwrap();
+    assert_eq!(
+        future::block_on(s.send_timeout(2, Duration::from_millis(1))),
+        Err(SendTimeoutError::Timeout(2)),
+    );
+}
+
+#[test]
+fn send_timeout_succeeds_with_room() {
+    let (s, r) = bounded(1);
+
+    assert_eq!(future::block_on(s.send_timeout(7, ms(1000))), Ok(()));
+    assert_eq!(future::block_on(r.recv()), Ok(7));
+}
+
+// A zero (already-elapsed) deadline must not stop a `send_timeout`/`recv_timeout` call from
+// taking the fast path if the operation could complete immediately: the op is always attempted
+// at least once before the deadline is checked.
+#[test]
+fn send_timeout_zero_duration_still_sends_if_ready() {
+    let (s, r) = bounded(1);
+
+    assert_eq!(future::block_on(s.send_timeout(7, Duration::ZERO)), Ok(()));
+    assert_eq!(future::block_on(r.recv_timeout(Duration::ZERO)), Ok(7));
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+#[test]
+fn recv_timeout_blocking_elapses_on_empty_channel() {
+    let (_s, r) = unbounded::<()>();
+
+    assert_eq!(
+        r.recv_timeout_blocking(Duration::from_millis(1)),
+        Err(RecvTimeoutError::Timeout),
+    );
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+#[test]
+fn send_timeout_blocking_elapses_on_full_channel() {
+    let (s, _r) = bounded(1);
+
+    s.send_blocking(1).unwrap();
+    assert_eq!(
+        s.send_timeout_blocking(2, Duration::from_millis(1)),
+        Err(SendTimeoutError::Timeout(2)),
+    );
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+#[test]
+fn recv_timeout_blocking_deregisters_waker_on_timeout() {
+    let (s, r) = unbounded();
+
+    assert_eq!(
+        r.recv_timeout_blocking(ms(200)),
+        Err(RecvTimeoutError::Timeout),
+    );
+
+    Parallel::new()
+        .add(move || {
+            assert_eq!(r.recv_blocking(), Ok(7));
+        })
+        .add(move || {
+            sleep(ms(500));
+            s.send_blocking(7).unwrap();
+        })
+        .run();
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+#[test]
+fn send_timeout_blocking_deregisters_waker_on_timeout() {
+    let (s, r) = bounded(1);
+    s.send_blocking(0).unwrap();
+
+    assert_eq!(
+        s.send_timeout_blocking(1, ms(200)),
+        Err(SendTimeoutError::Timeout(1)),
+    );
+
+    Parallel::new()
+        .add(move || {
+            assert_eq!(s.send_blocking(2), Ok(()));
+        })
+        .add(move || {
+            sleep(ms(500));
+            r.recv_blocking().unwrap();
+            r.recv_blocking().unwrap();
+        })
+        .run();
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn recv_timeout_deregisters_waker_on_timeout() {
+    // After a `recv_timeout()` call times out, it must deregister its listener so that a send
+    // arriving afterwards wakes the *next* waiter instead of a thread that already gave up. If
+    // it didn't, a later plain `recv()` on the same channel could be left waiting forever for a
+    // notification that was already consumed by the timed-out call.
+    let (s, r) = unbounded();
+
+    assert_eq!(
+        future::block_on(r.recv_timeout(ms(200))),
+        Err(RecvTimeoutError::Timeout),
+    );
+
+    Parallel::new()
+        .add(move || {
+            assert_eq!(future::block_on(r.recv()), Ok(7));
+        })
+        .add(move || {
+            sleep(ms(500));
+            future::block_on(s.send(7)).unwrap();
+        })
+        .run();
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn send_timeout_deregisters_waker_on_timeout() {
+    let (s, r) = bounded(1);
+    s.try_send(0).unwrap();
+
+    assert_eq!(
+        future::block_on(s.send_timeout(1, ms(200))),
+        Err(SendTimeoutError::Timeout(1)),
+    );
+
+    Parallel::new()
+        .add(move || {
+            assert_eq!(future::block_on(s.send(2)), Ok(()));
+        })
+        .add(move || {
+            sleep(ms(500));
+            future::block_on(r.recv()).unwrap();
+            future::block_on(r.recv()).unwrap();
+        })
+        .run();
+}
@@ -0,0 +1,74 @@
+#![allow(clippy::bool_assert_comparison, unused_imports)]
+
+use std::time::{Duration, Instant};
+
+use async_channel::{after, tick, Select};
+use futures_lite::future;
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn after_delivers_once() {
+    let start = Instant::now();
+    let r = after(Duration::from_millis(50));
+
+    let fired = future::block_on(r.recv()).unwrap();
+    assert!(fired >= start);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    // Exactly one instant is ever delivered.
+    assert!(future::block_on(r.recv()).is_err());
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn after_can_be_selected_against_a_data_channel() {
+    let (_s, r) = async_channel::unbounded::<()>();
+    let timeout = after(Duration::from_millis(1));
+
+    future::block_on(async {
+        let mut select = Select::new();
+        select.recv(&r);
+        select.recv(&timeout);
+
+        let op = select.select().await;
+        assert_eq!(op.index(), 1);
+    });
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn tick_delivers_repeatedly_without_accumulating_drift() {
+    const TICKS: u32 = 5;
+    let period = Duration::from_millis(20);
+
+    let start = Instant::now();
+    let ticker = tick(period);
+
+    for _ in 0..TICKS {
+        future::block_on(ticker.recv()).unwrap();
+    }
+
+    // Deadlines are anchored to `start`, not the previous delivery, so `TICKS` ticks should take
+    // roughly `TICKS * period`, not noticeably more (which would indicate drift accumulating from
+    // scheduling delay between deliveries).
+    let elapsed = start.elapsed();
+    assert!(elapsed >= period * TICKS);
+    assert!(elapsed < period * TICKS * 3);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn tick_skips_unconsumed_ticks_instead_of_queuing_them() {
+    let ticker = tick(Duration::from_millis(10));
+
+    // Let several ticks come and go without ever calling `recv()`.
+    std::thread::sleep(Duration::from_millis(100));
+
+    // Only one tick (the first one not picked up) is waiting; it isn't a backlog of ten-odd
+    // queued ticks.
+    assert!(future::block_on(ticker.recv()).is_ok());
+    assert_eq!(ticker.try_recv(), Err(async_channel::TryRecvError::Empty));
+}
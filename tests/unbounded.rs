@@ -362,6 +362,16 @@ fn weak() {
     // Create a weak sender/receiver pair.
     let (weak_s, weak_r) = (s.downgrade(), r.downgrade());
 
+    // Two weak handles downgraded from the same channel compare equal; a weak handle from an
+    // unrelated channel doesn't.
+    {
+        let (other_s, other_r) = unbounded::<usize>();
+        assert!(weak_s.same_channel(&weak_s.clone()));
+        assert!(weak_r.same_channel(&weak_r.clone()));
+        assert!(!weak_s.same_channel(&other_s.downgrade()));
+        assert!(!weak_r.same_channel(&other_r.downgrade()));
+    }
+
     // Upgrade and send.
     {
         let s = weak_s.upgrade().unwrap();